@@ -0,0 +1,31 @@
+use confik::Configuration;
+
+/// Optional TLS settings for the Postgres connection.
+///
+/// Each credential can be supplied either as a filesystem path or, for
+/// containerised deployments where mounting files is awkward, as a
+/// base64-encoded environment variable (`CA_PEM_B64`, `CLIENT_PKS_B64`).
+#[derive(Debug, Default, Configuration)]
+pub struct TlsConfig {
+    pub ca_pem_path: Option<String>,
+    pub client_pks_path: Option<String>,
+    pub client_pks_pass: Option<String>,
+}
+
+#[derive(Debug, Default, Configuration)]
+pub struct Config {
+    pub server_addr: String,
+    pub pg: deadpool_postgres::Config,
+    pub tls: Option<TlsConfig>,
+    /// Maximum number of pooled connections. Defaults to a multiple of the
+    /// available CPUs (see [`default_pool_size`]) when left unset.
+    pub max_pool_size: Option<usize>,
+}
+
+/// Number of connections per CPU used when `max_pool_size` is not configured.
+const POOL_SIZE_PER_CPU: usize = 4;
+
+/// Default pool size scaled to the host so concurrency tracks the hardware.
+pub fn default_pool_size() -> usize {
+    num_cpus::get() * POOL_SIZE_PER_CPU
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, HttpResponseBuilder, ResponseError};
+use deadpool_postgres::PoolError;
+use serde::Serialize;
+use tokio_pg_mapper::Error as PGMapperError;
+use tokio_postgres::error::Error as PGError;
+
+/// The category of an application error, used to pick an HTTP status code and
+/// a sensible default user-facing message.
+#[derive(Debug)]
+pub enum ErrorKind {
+    DbError,
+    NotFoundError,
+    PoolError,
+    ValidationError,
+    ServiceUnavailable,
+}
+
+/// Application error carrying a category, an optional user-facing `message`
+/// and an optional internal `cause` that is only ever logged, never returned.
+#[derive(Debug)]
+pub struct MyError {
+    pub kind: ErrorKind,
+    pub message: Option<String>,
+    pub cause: Option<String>,
+}
+
+impl MyError {
+    fn new(kind: ErrorKind, message: Option<String>, cause: Option<String>) -> Self {
+        MyError {
+            kind,
+            message,
+            cause,
+        }
+    }
+
+    pub fn not_found() -> Self {
+        MyError::new(ErrorKind::NotFoundError, None, None)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        MyError::new(ErrorKind::ValidationError, Some(message.into()), None)
+    }
+
+    pub fn unavailable(cause: impl Into<String>) -> Self {
+        MyError::new(ErrorKind::ServiceUnavailable, None, Some(cause.into()))
+    }
+
+    fn default_message(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::DbError => "internal database error",
+            ErrorKind::NotFoundError => "resource not found",
+            ErrorKind::PoolError => "database connection unavailable",
+            ErrorKind::ValidationError => "invalid request",
+            ErrorKind::ServiceUnavailable => "service unavailable",
+        }
+    }
+}
+
+impl fmt::Display for MyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.message.as_deref().unwrap_or(self.default_message());
+        match &self.cause {
+            Some(cause) => write!(f, "{message}: {cause}"),
+            None => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for MyError {}
+
+impl From<PGError> for MyError {
+    fn from(err: PGError) -> Self {
+        MyError::new(ErrorKind::DbError, None, Some(err.to_string()))
+    }
+}
+
+impl From<PGMapperError> for MyError {
+    fn from(err: PGMapperError) -> Self {
+        MyError::new(ErrorKind::DbError, None, Some(err.to_string()))
+    }
+}
+
+impl From<PoolError> for MyError {
+    fn from(err: PoolError) -> Self {
+        MyError::new(ErrorKind::PoolError, None, Some(err.to_string()))
+    }
+}
+
+/// JSON envelope returned to clients for any error response.
+#[derive(Serialize)]
+struct ErrorResponse {
+    message: String,
+    status: u16,
+}
+
+impl ResponseError for MyError {
+    fn status_code(&self) -> StatusCode {
+        match self.kind {
+            ErrorKind::NotFoundError => StatusCode::NOT_FOUND,
+            ErrorKind::ValidationError => StatusCode::BAD_REQUEST,
+            ErrorKind::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorKind::DbError | ErrorKind::PoolError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        let message = self
+            .message
+            .clone()
+            .unwrap_or_else(|| self.default_message().to_owned());
+
+        HttpResponseBuilder::new(status).json(ErrorResponse {
+            message,
+            status: status.as_u16(),
+        })
+    }
+}
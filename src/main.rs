@@ -1,39 +1,190 @@
 use actix_web::{web, App, Error, HttpResponse, HttpServer};
+use base64::engine::{general_purpose::STANDARD as BASE64, Engine as _};
 use confik::{Configuration as _, EnvSource};
 use deadpool_postgres::{Client, Pool};
 use dotenvy::dotenv;
-use tokio_postgres::NoTls;
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use tokio_postgres::{config::SslMode, NoTls};
 
-use crate::config::Config;
+use crate::config::{default_pool_size, Config, TlsConfig};
 
 mod config;
 mod error;
 mod model;
 mod repository;
 
-use self::{error::MyError, model::Transaction};
+use uuid::Uuid;
 
-pub async fn get_transactions(db_pool: web::Data<Pool>) -> Result<HttpResponse, Error> {
-    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+use self::{
+    error::MyError,
+    model::{NewTransaction, TransactionQuery},
+};
 
-    let transactions = repository::get_transactions(&client).await?;
+/// Load a credential either from `path` or, failing that, from the
+/// base64-encoded environment variable named `b64_env`.
+fn load_credential(path: Option<&str>, b64_env: &str) -> std::io::Result<Option<Vec<u8>>> {
+    if let Some(path) = path {
+        return Ok(Some(std::fs::read(path)?));
+    }
+    if let Ok(encoded) = std::env::var(b64_env) {
+        let decoded = BASE64.decode(encoded.trim()).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+        })?;
+        return Ok(Some(decoded));
+    }
+    Ok(None)
+}
+
+/// Build a `MakeTlsConnector` from the optional TLS configuration, wiring up
+/// a CA root certificate and a PKCS#12 client identity when supplied.
+fn build_tls_connector(tls: Option<&TlsConfig>) -> std::io::Result<MakeTlsConnector> {
+    let mut builder = TlsConnector::builder();
+
+    if let Some(tls) = tls {
+        if let Some(ca) = load_credential(tls.ca_pem_path.as_deref(), "CA_PEM_B64")? {
+            let cert = Certificate::from_pem(&ca)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            builder.add_root_certificate(cert);
+        }
+
+        if let Some(pks) = load_credential(tls.client_pks_path.as_deref(), "CLIENT_PKS_B64")? {
+            let pass = tls
+                .client_pks_pass
+                .clone()
+                .or_else(|| std::env::var("CLIENT_PKS_PASS").ok())
+                .unwrap_or_default();
+            let identity = Identity::from_pkcs12(&pks, &pass)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            builder.identity(identity);
+        }
+    }
+
+    let connector = builder
+        .build()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+
+    Ok(MakeTlsConnector::new(connector))
+}
+
+/// Create the connection pool, selecting `NoTls` only when the parsed
+/// connection config explicitly disables SSL and otherwise negotiating TLS.
+fn create_pool(config: &Config) -> std::io::Result<Pool> {
+    let mut pg = config.pg.clone();
+    let max_size = config.max_pool_size.unwrap_or_else(default_pool_size);
+    pg.pool = Some(deadpool_postgres::PoolConfig::new(max_size));
+
+    let pg_config = pg
+        .get_pg_config()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+
+    let pool = match pg_config.get_ssl_mode() {
+        SslMode::Disable => pg.create_pool(None, NoTls),
+        _ => {
+            let connector = build_tls_connector(config.tls.as_ref())?;
+            pg.create_pool(None, connector)
+        }
+    };
+
+    pool.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+}
+
+pub async fn get_transactions(
+    query: web::Query<TransactionQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let transactions = repository::get_transactions(&client, &query).await?;
 
     Ok(HttpResponse::Ok().json(transactions))
 }
 
+pub async fn get_transaction(
+    id: web::Path<Uuid>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let transaction = repository::get_transaction_by_id(&client, id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(transaction))
+}
+
 pub async fn add_transaction(
-    transaction: web::Json<Transaction>,
+    transaction: web::Json<NewTransaction>,
     db_pool: web::Data<Pool>,
 ) -> Result<HttpResponse, Error> {
-    let transaction_data: Transaction = transaction.into_inner();
+    let transaction_data: NewTransaction = transaction.into_inner();
 
-    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
 
     let new_transaction = repository::add_transaction(&client, transaction_data).await?;
 
     Ok(HttpResponse::Ok().json(new_transaction))
 }
 
+pub async fn update_transaction(
+    id: web::Path<Uuid>,
+    transaction: web::Json<NewTransaction>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let transaction_data: NewTransaction = transaction.into_inner();
+
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let updated = repository::update_transaction(&client, id.into_inner(), transaction_data).await?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+pub async fn delete_transaction(
+    id: web::Path<Uuid>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let deleted = repository::delete_transaction(&client, id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(deleted))
+}
+
+pub async fn get_wallet_balance(
+    wallet: web::Path<String>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let balance = repository::get_wallet_balance(&client, &wallet).await?;
+
+    Ok(HttpResponse::Ok().json(balance))
+}
+
+pub async fn get_wallet_ledger(
+    wallet: web::Path<String>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::from)?;
+
+    let ledger = repository::get_wallet_ledger(&client, &wallet).await?;
+
+    Ok(HttpResponse::Ok().json(ledger))
+}
+
+pub async fn health(db_pool: web::Data<Pool>) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool
+        .get()
+        .await
+        .map_err(|err| MyError::unavailable(err.to_string()))?;
+
+    client
+        .query_one("SELECT 1", &[])
+        .await
+        .map_err(|err| MyError::unavailable(err.to_string()))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
@@ -43,14 +194,31 @@ async fn main() -> std::io::Result<()> {
         .try_build()
         .unwrap();
 
-    let pool = config.pg.create_pool(None, NoTls).unwrap();
+    let pool = create_pool(&config)?;
 
     let server = HttpServer::new(move || {
-        App::new().app_data(web::Data::new(pool.clone())).service(
-            web::resource("/transactions")
-                .route(web::post().to(add_transaction))
-                .route(web::get().to(get_transactions)),
-        )
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .route("/health", web::get().to(health))
+            .service(
+                web::resource("/transactions")
+                    .route(web::post().to(add_transaction))
+                    .route(web::get().to(get_transactions)),
+            )
+            .service(
+                web::resource("/transactions/{id}")
+                    .route(web::get().to(get_transaction))
+                    .route(web::put().to(update_transaction))
+                    .route(web::delete().to(delete_transaction)),
+            )
+            .service(
+                web::resource("/wallets/{wallet}/balance")
+                    .route(web::get().to(get_wallet_balance)),
+            )
+            .service(
+                web::resource("/wallets/{wallet}/ledger")
+                    .route(web::get().to(get_wallet_ledger)),
+            )
     })
     .bind(config.server_addr.clone())?
     .run();
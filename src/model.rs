@@ -1,11 +1,69 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio_pg_mapper_derive::PostgresMapper;
+use uuid::Uuid;
+
+/// Query parameters accepted by the list endpoint for pagination and
+/// filtering. Every field is optional; absent fields are simply not applied.
+#[derive(Deserialize)]
+pub struct TransactionQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub trx_type: Option<String>,
+    pub trx_subtype: Option<String>,
+    pub wallet_from: Option<String>,
+    pub wallet_to: Option<String>,
+    pub datetime_from: Option<String>,
+    pub datetime_to: Option<String>,
+}
+
+/// Client payload for creating a transaction. `id` and `datetime` are
+/// deliberately absent: they are generated server-side so clients cannot
+/// collide ids or supply malformed timestamps.
+#[derive(Deserialize, Serialize)]
+pub struct NewTransaction {
+    pub trx_type: String,
+    pub trx_subtype: String,
+    pub wallet_from: Option<String>,
+    pub wallet_to: Option<String>,
+    pub name: String,
+    pub amount: i64,
+    pub fee: Option<i64>,
+    pub description: Option<String>,
+}
+
+/// Net balance of a wallet, derived from its incoming and outgoing
+/// transactions (fees are borne by the sending wallet).
+#[derive(Serialize)]
+pub struct WalletBalance {
+    pub wallet: String,
+    pub balance: i64,
+}
+
+/// A transaction as it appears in a wallet's ledger, carrying the cumulative
+/// `running_balance` over every transaction of the wallet up to and including
+/// this row's `datetime`; rows sharing a `datetime` share one running balance.
+#[derive(Deserialize, PostgresMapper, Serialize)]
+#[pg_mapper(table = "transactions")]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub datetime: DateTime<Utc>,
+    pub trx_type: String,
+    pub trx_subtype: String,
+    pub wallet_from: Option<String>,
+    pub wallet_to: Option<String>,
+    pub name: String,
+    pub amount: i64,
+    pub fee: Option<i64>,
+    pub description: Option<String>,
+    pub running_balance: i64,
+}
 
 #[derive(Deserialize, PostgresMapper, Serialize)]
 #[pg_mapper(table = "transactions")]
 pub struct Transaction {
-    pub id: String,
-    pub datetime: String,
+    pub id: Uuid,
+    pub datetime: DateTime<Utc>,
     pub trx_type: String,
     pub trx_subtype: String,
     pub wallet_from: Option<String>,
@@ -1,40 +1,146 @@
+use chrono::{DateTime, Utc};
 use deadpool_postgres::Client;
 use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::types::ToSql;
+use uuid::Uuid;
 
-use crate::{error::MyError, model::Transaction};
+use crate::{
+    error::MyError,
+    model::{LedgerEntry, NewTransaction, Transaction, TransactionQuery, WalletBalance},
+};
 
-pub async fn get_transactions(client: &Client) -> Result<Vec<Transaction>, MyError> {
-    let stmt = r#"
-        SELECT
-            id,
-            datetime,
-            trx_type,
-            trx_subtype,
-            wallet_from,
-            wallet_to,
-            name,
-            amount,
-            fee,
-            description
-        FROM transactions
-    "#;
-    let stmt = client.prepare(&stmt).await.unwrap();
+/// Signed per-row contribution of a transaction to a wallet's balance: it is
+/// credited for funds received and debited for funds sent plus the fee it
+/// paid. A self-transfer (same wallet on both sides) nets to just `-fee`, and
+/// `NULL` fees are treated as zero.
+const BALANCE_EXPR: &str = r#"
+    CASE WHEN wallet_to = $1 THEN amount ELSE 0 END
+  - CASE WHEN wallet_from = $1 THEN amount + COALESCE(fee, 0) ELSE 0 END
+"#;
+
+/// Parse an RFC 3339 / ISO-8601 bound from a query string, surfacing a
+/// `ValidationError` (400) rather than a database error on malformed input.
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>, MyError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|err| MyError::validation(format!("invalid datetime '{value}': {err}")))
+}
+
+/// Columns selected/returned for a transaction, kept in one place so every
+/// query maps onto the same `PostgresMapper` layout.
+const COLUMNS: &str = r#"
+    id,
+    datetime,
+    trx_type,
+    trx_subtype,
+    wallet_from,
+    wallet_to,
+    name,
+    amount,
+    fee,
+    description
+"#;
+
+/// Upper bound applied to `limit` so a client cannot ask for an unbounded scan.
+const MAX_LIMIT: i64 = 1000;
+const DEFAULT_LIMIT: i64 = 100;
+
+pub async fn get_transactions(
+    client: &Client,
+    query: &TransactionQuery,
+) -> Result<Vec<Transaction>, MyError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(0, MAX_LIMIT);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let datetime_from = query
+        .datetime_from
+        .as_deref()
+        .map(parse_datetime)
+        .transpose()?;
+    let datetime_to = query
+        .datetime_to
+        .as_deref()
+        .map(parse_datetime)
+        .transpose()?;
+
+    let mut sql = format!("SELECT {COLUMNS} FROM transactions");
+    let mut params: Vec<&(dyn ToSql + Sync)> = Vec::new();
+    let mut clauses: Vec<String> = Vec::new();
+
+    if let Some(ref trx_type) = query.trx_type {
+        params.push(trx_type);
+        clauses.push(format!("trx_type = ${}", params.len()));
+    }
+    if let Some(ref trx_subtype) = query.trx_subtype {
+        params.push(trx_subtype);
+        clauses.push(format!("trx_subtype = ${}", params.len()));
+    }
+    if let Some(ref wallet_from) = query.wallet_from {
+        params.push(wallet_from);
+        clauses.push(format!("wallet_from = ${}", params.len()));
+    }
+    if let Some(ref wallet_to) = query.wallet_to {
+        params.push(wallet_to);
+        clauses.push(format!("wallet_to = ${}", params.len()));
+    }
+    if let Some(ref datetime_from) = datetime_from {
+        params.push(datetime_from);
+        clauses.push(format!("datetime >= ${}", params.len()));
+    }
+    if let Some(ref datetime_to) = datetime_to {
+        params.push(datetime_to);
+        clauses.push(format!("datetime <= ${}", params.len()));
+    }
+
+    if !clauses.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&clauses.join(" AND "));
+    }
+
+    sql.push_str(" ORDER BY datetime DESC");
+
+    params.push(&limit);
+    sql.push_str(&format!(" LIMIT ${}", params.len()));
+    params.push(&offset);
+    sql.push_str(&format!(" OFFSET ${}", params.len()));
+
+    let stmt = client.prepare(&sql).await?;
 
     let results = client
-        .query(&stmt, &[])
+        .query(&stmt, &params)
         .await?
         .iter()
-        .map(|row| Transaction::from_row_ref(row).unwrap())
-        .collect::<Vec<Transaction>>();
+        .map(Transaction::from_row_ref)
+        .collect::<Result<Vec<Transaction>, _>>()?;
 
     Ok(results)
 }
 
+pub async fn get_transaction_by_id(client: &Client, id: Uuid) -> Result<Transaction, MyError> {
+    let stmt = format!("SELECT {COLUMNS} FROM transactions WHERE id = $1");
+    let stmt = client.prepare(&stmt).await?;
+
+    client
+        .query(&stmt, &[&id])
+        .await?
+        .iter()
+        .map(Transaction::from_row_ref)
+        .collect::<Result<Vec<Transaction>, _>>()?
+        .pop()
+        .ok_or_else(MyError::not_found)
+}
+
 pub async fn add_transaction(
     client: &Client,
-    transaction: Transaction,
+    transaction: NewTransaction,
 ) -> Result<Transaction, MyError> {
-    let _stmt = r#"
+    // `id` and `datetime` are generated here rather than taken from the
+    // client so ids never collide and timestamps are always well-formed.
+    let id = Uuid::new_v4();
+    let datetime = Utc::now();
+
+    let _stmt = format!(
+        r#"
         INSERT INTO transactions(
             id,
             datetime,
@@ -48,26 +154,62 @@ pub async fn add_transaction(
             description
         )
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-        RETURNING
-            id,
-            datetime,
-            trx_type,
-            trx_subtype,
-            wallet_from,
-            wallet_to,
-            name,
-            amount,
-            fee,
-            description
-    "#;
-    let stmt = client.prepare(&_stmt).await.unwrap();
+        RETURNING {COLUMNS}
+    "#
+    );
+    let stmt = client.prepare(&_stmt).await?;
+
+    client
+        .query(
+            &stmt,
+            &[
+                &id,
+                &datetime,
+                &transaction.trx_type,
+                &transaction.trx_subtype,
+                &transaction.wallet_from,
+                &transaction.wallet_to,
+                &transaction.name,
+                &transaction.amount,
+                &transaction.fee,
+                &transaction.description,
+            ],
+        )
+        .await?
+        .iter()
+        .map(Transaction::from_row_ref)
+        .collect::<Result<Vec<Transaction>, _>>()?
+        .pop()
+        .ok_or_else(MyError::not_found)
+}
+
+pub async fn update_transaction(
+    client: &Client,
+    id: Uuid,
+    transaction: NewTransaction,
+) -> Result<Transaction, MyError> {
+    let _stmt = format!(
+        r#"
+        UPDATE transactions SET
+            trx_type = $2,
+            trx_subtype = $3,
+            wallet_from = $4,
+            wallet_to = $5,
+            name = $6,
+            amount = $7,
+            fee = $8,
+            description = $9
+        WHERE id = $1
+        RETURNING {COLUMNS}
+    "#
+    );
+    let stmt = client.prepare(&_stmt).await?;
 
     client
         .query(
             &stmt,
             &[
-                &transaction.id,
-                &transaction.datetime,
+                &id,
                 &transaction.trx_type,
                 &transaction.trx_subtype,
                 &transaction.wallet_from,
@@ -80,8 +222,66 @@ pub async fn add_transaction(
         )
         .await?
         .iter()
-        .map(|row| Transaction::from_row_ref(row).unwrap())
-        .collect::<Vec<Transaction>>()
+        .map(Transaction::from_row_ref)
+        .collect::<Result<Vec<Transaction>, _>>()?
+        .pop()
+        .ok_or_else(MyError::not_found)
+}
+
+pub async fn get_wallet_balance(client: &Client, wallet: &str) -> Result<WalletBalance, MyError> {
+    let stmt = format!(
+        r#"
+        SELECT COALESCE(SUM({BALANCE_EXPR}), 0)::BIGINT AS balance
+        FROM transactions
+        WHERE wallet_from = $1 OR wallet_to = $1
+    "#
+    );
+    let stmt = client.prepare(&stmt).await?;
+
+    let row = client.query_one(&stmt, &[&wallet]).await?;
+
+    Ok(WalletBalance {
+        wallet: wallet.to_owned(),
+        balance: row.get("balance"),
+    })
+}
+
+pub async fn get_wallet_ledger(
+    client: &Client,
+    wallet: &str,
+) -> Result<Vec<LedgerEntry>, MyError> {
+    let stmt = format!(
+        r#"
+        SELECT
+            {COLUMNS},
+            (SUM({BALANCE_EXPR}) OVER (ORDER BY datetime))::BIGINT AS running_balance
+        FROM transactions
+        WHERE wallet_from = $1 OR wallet_to = $1
+        ORDER BY datetime
+    "#
+    );
+    let stmt = client.prepare(&stmt).await?;
+
+    let results = client
+        .query(&stmt, &[&wallet])
+        .await?
+        .iter()
+        .map(LedgerEntry::from_row_ref)
+        .collect::<Result<Vec<LedgerEntry>, _>>()?;
+
+    Ok(results)
+}
+
+pub async fn delete_transaction(client: &Client, id: Uuid) -> Result<Transaction, MyError> {
+    let stmt = format!("DELETE FROM transactions WHERE id = $1 RETURNING {COLUMNS}");
+    let stmt = client.prepare(&stmt).await?;
+
+    client
+        .query(&stmt, &[&id])
+        .await?
+        .iter()
+        .map(Transaction::from_row_ref)
+        .collect::<Result<Vec<Transaction>, _>>()?
         .pop()
-        .ok_or(MyError::NotFound)
+        .ok_or_else(MyError::not_found)
 }